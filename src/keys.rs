@@ -2,26 +2,75 @@
 ///
 use bip32::{ChildNumber, XPrv};
 use bip39::Mnemonic;
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::{ChaCha20Poly1305, Nonce};
+use hkdf::Hkdf;
 use rand::{thread_rng, RngCore};
+use secp256k1::ecdh::shared_secret_point;
 use secp256k1::hashes::{sha256, Hash};
-use secp256k1::{All, KeyPair, Parity, Scalar, Secp256k1, SecretKey, XOnlyPublicKey};
-
-/// Keys at a given level: a pair of keypairs (visible and hidden)
-#[derive(Clone, Copy, Debug)]
+use secp256k1::{
+    schnorr, All, KeyPair, Message, Parity, PublicKey, Scalar, Secp256k1, SecretKey,
+    XOnlyPublicKey,
+};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use zeroize::{Zeroize, ZeroizeOnDrop, Zeroizing};
+
+/// Keys at a given level: a secret scalar and its public counterpart, for
+/// both the visible and the hidden key of the level.
+///
+/// The secret scalars are kept in `Zeroizing` buffers and the struct derives
+/// `ZeroizeOnDrop`, so both scalars are scrubbed from memory as soon as a
+/// `LevelKeys` (or the `KeyState` holding it) goes out of scope. The public
+/// keys are not secret and are left untouched.
+#[derive(Clone, Debug, Zeroize, ZeroizeOnDrop)]
 struct LevelKeys {
-    /// The visible keypair (denoted A)
-    vis: KeyPair,
-    /// The hidden keypair (denoted A')
-    hid: KeyPair,
+    /// The visible secret scalar (denoted A)
+    vis: Zeroizing<[u8; 32]>,
+    /// The visible public key (denoted A)
+    #[zeroize(skip)]
+    vis_pub: XOnlyPublicKey,
+    /// The hidden secret scalar (denoted A')
+    hid: Zeroizing<[u8; 32]>,
+    /// The hidden public key (denoted A')
+    #[zeroize(skip)]
+    hid_pub: XOnlyPublicKey,
 }
 
 impl LevelKeys {
+    /// Build a level from its two secret keys, caching their public keys.
+    fn new(secp: &Secp256k1<All>, vis_sk: SecretKey, hid_sk: SecretKey) -> Self {
+        let vis_pub = vis_sk.x_only_public_key(secp).0;
+        let hid_pub = hid_sk.x_only_public_key(secp).0;
+        Self {
+            vis: Zeroizing::new(vis_sk.secret_bytes()),
+            vis_pub,
+            hid: Zeroizing::new(hid_sk.secret_bytes()),
+            hid_pub,
+        }
+    }
+
     pub fn vis_pubkey(&self) -> XOnlyPublicKey {
-        self.vis.x_only_public_key().0
+        self.vis_pub
     }
 
     pub fn hid_pubkey(&self) -> XOnlyPublicKey {
-        self.hid.x_only_public_key().0
+        self.hid_pub
+    }
+
+    /// Reconstitute the visible secret key behind a guard that zeroizes it
+    /// on drop.
+    fn vis_secret_key(&self) -> SecretKeyGuard {
+        SecretKeyGuard::new(
+            SecretKey::from_slice(&*self.vis).expect("stored bytes are a valid secret key"),
+        )
+    }
+
+    /// Reconstitute the hidden secret key; see `vis_secret_key`.
+    fn hid_secret_key(&self) -> SecretKeyGuard {
+        SecretKeyGuard::new(
+            SecretKey::from_slice(&*self.hid).expect("stored bytes are a valid secret key"),
+        )
     }
 }
 
@@ -29,6 +78,10 @@ impl LevelKeys {
 pub const N_DEFAULT: usize = 256;
 
 /// Complete state of NIP-41 keys
+///
+/// Derives `ZeroizeOnDrop` so that every level's secret scalars are scrubbed
+/// from memory as soon as the state is dropped, e.g. after a key rotation.
+#[derive(Debug, Zeroize, ZeroizeOnDrop)]
 pub struct KeyState {
     /// The N key levels
     k: Vec<LevelKeys>,
@@ -36,6 +89,33 @@ pub struct KeyState {
     n: usize,
 }
 
+/// A handle to secret-key material that scrubs itself from memory on drop.
+///
+/// `SecretKey` itself is `Copy` and so can never implement `Drop`; this
+/// newtype instead keeps the secret in a `Zeroizing` byte buffer and only
+/// ever hands out transient, reconstructed `SecretKey` copies, the same
+/// pattern `LevelKeys` uses for its own storage.
+pub struct SecretKeyGuard(Zeroizing<[u8; 32]>);
+
+impl SecretKeyGuard {
+    fn new(sk: SecretKey) -> Self {
+        Self(Zeroizing::new(sk.secret_bytes()))
+    }
+
+    /// Reconstitute the underlying secret key for use with secp256k1 APIs.
+    /// The returned value is a short-lived, bare `SecretKey` copy; only the
+    /// guard's own storage is guaranteed to be scrubbed on drop.
+    pub fn as_secret_key(&self) -> SecretKey {
+        SecretKey::from_slice(&*self.0).expect("stored bytes are a valid secret key")
+    }
+}
+
+impl std::fmt::Debug for SecretKeyGuard {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("SecretKeyGuard(..)")
+    }
+}
+
 #[derive(Debug, PartialEq, thiserror::Error)]
 pub enum Error {
     /// No more levels left, ran out of pre-defined keys
@@ -47,6 +127,73 @@ pub enum Error {
     /// Error processing BIP39 mnemonic
     #[error(transparent)]
     Bip39(#[from] bip39::Error),
+    /// Checkpoint's seed fingerprint doesn't match the supplied mnemonic
+    #[error("Checkpoint does not match the supplied mnemonic")]
+    CheckpointMismatch,
+    /// Checkpoint's level index is out of range for the derived key state
+    #[error("Checkpoint level is out of range")]
+    InvalidCheckpointLevel,
+    /// Sealing a backup blob failed
+    #[error("Failed to encrypt backup")]
+    EncryptionFailed,
+    /// Opening a backup blob failed (wrong key, or the blob was tampered with)
+    #[error("Failed to decrypt backup")]
+    DecryptionFailed,
+}
+
+/// A compact, persistable snapshot of rotation progress.
+///
+/// Holds the current level index plus a fingerprint of the level-0 master
+/// visible pubkey, but no secret material, so it's safe to write to disk.
+/// Pass it together with the original mnemonic to
+/// [`KeyManager::restore_from_mnemonic_and_checkpoint`] to resume a session
+/// at the right level instead of rewinding to level N-1.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Checkpoint {
+    /// The current level index at the time the checkpoint was taken
+    n: usize,
+    /// Fingerprint of the level-0 master visible pubkey
+    #[serde(with = "fingerprint_serde")]
+    fingerprint: [u8; 32],
+}
+
+impl Checkpoint {
+    /// Hash a level-0 visible pubkey into a checkpoint fingerprint
+    fn fingerprint_of(level_0_vis_pubkey: &XOnlyPublicKey) -> [u8; 32] {
+        sha256::Hash::hash(&level_0_vis_pubkey.serialize()).to_byte_array()
+    }
+}
+
+/// (De)serialize a 32-byte fingerprint as a hex string for human-readable
+/// formats (e.g. JSON), and as a fixed-length tuple of bytes otherwise.
+mod fingerprint_serde {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S>(bytes: &[u8; 32], serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        if serializer.is_human_readable() {
+            hex::encode(bytes).serialize(serializer)
+        } else {
+            bytes.serialize(serializer)
+        }
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<[u8; 32], D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        if deserializer.is_human_readable() {
+            let s = String::deserialize(deserializer)?;
+            let bytes = hex::decode(s).map_err(serde::de::Error::custom)?;
+            bytes
+                .try_into()
+                .map_err(|_| serde::de::Error::custom("fingerprint must be 32 bytes"))
+        } else {
+            <[u8; 32]>::deserialize(deserializer)
+        }
+    }
 }
 
 impl KeyState {
@@ -55,15 +202,40 @@ impl KeyState {
         Ok(self.k[self.n].vis_pubkey())
     }
 
-    /// Obtain the current secret key; security sensitive!
-    pub fn current_visible_secret_key(&self) -> Result<SecretKey, Error> {
-        Ok(self.k[self.n].vis.secret_key())
+    /// Obtain the current secret key; security sensitive! The returned guard
+    /// zeroizes its contents when dropped.
+    pub fn current_visible_secret_key(&self) -> Result<SecretKeyGuard, Error> {
+        Ok(self.k[self.n].vis_secret_key())
+    }
+
+    /// Sign a 32-byte event hash with the current level's visible key, using
+    /// BIP340 Schnorr with a deterministic nonce (derived by tagged-hashing
+    /// the secret key, message and auxiliary data, no fresh randomness), so
+    /// repeated signatures over the same hash are reproducible.
+    pub fn sign_current(
+        &self,
+        secp: &Secp256k1<All>,
+        msg: &[u8; 32],
+    ) -> Result<schnorr::Signature, Error> {
+        let vis_sk = self.k[self.n].vis_secret_key();
+        let keypair = KeyPair::from_secret_key(secp, &vis_sk.as_secret_key());
+        let message = Message::from_slice(msg).expect("event hash is always 32 bytes");
+        Ok(secp.sign_schnorr_no_aux_rand(&message, &keypair))
     }
 
     pub fn levels(&self) -> usize {
         self.k.len()
     }
 
+    /// Take a compact, non-secret checkpoint of the current rotation
+    /// progress, suitable for persisting across restarts.
+    pub fn checkpoint(&self) -> Checkpoint {
+        Checkpoint {
+            n: self.n,
+            fingerprint: Checkpoint::fingerprint_of(&self.k[0].vis_pubkey()),
+        }
+    }
+
     /// Invalidate the current key; reveal it's secret counterpart,
     /// and (optionally) switch to a new one (the previous one in the pre-generated levels).
     /// Returns these pubkeys:
@@ -136,39 +308,76 @@ impl KeyManager {
         self.generate_from_master_seed(seed)
     }
 
+    /// Rebuild a `KeyState` from a mnemonic and a previously saved
+    /// `Checkpoint`, restoring the current level instead of rewinding to
+    /// level N-1. Rejects the checkpoint if its fingerprint doesn't match
+    /// the master key derived from `mnemonic_str`.
+    pub fn restore_from_mnemonic_and_checkpoint(
+        &self,
+        mnemonic_str: &str,
+        checkpoint: &Checkpoint,
+    ) -> Result<KeyState, Error> {
+        let state = self.generate_from_mnemonic(mnemonic_str)?;
+        Self::apply_checkpoint(state, checkpoint)
+    }
+
+    /// Fast-forward a freshly derived `KeyState` to the level recorded in
+    /// `checkpoint`, rejecting it if the fingerprint doesn't match.
+    fn apply_checkpoint(mut state: KeyState, checkpoint: &Checkpoint) -> Result<KeyState, Error> {
+        let fingerprint = Checkpoint::fingerprint_of(&state.k[0].vis_pubkey());
+        if fingerprint != checkpoint.fingerprint {
+            return Err(Error::CheckpointMismatch);
+        }
+        if checkpoint.n >= state.levels() {
+            return Err(Error::InvalidCheckpointLevel);
+        }
+        state.n = checkpoint.n;
+        Ok(state)
+    }
+
     /// Generate state from a 64-byte master seed
     pub fn generate_from_master_seed(&self, master_seed: [u8; 64]) -> Result<KeyState, Error> {
+        let mut master_seed = master_seed;
         // generate hidden keys HD (hierarchically deterministically, BIP32)
         let mut sk = Vec::new();
-        // for optimization, derive common part only once
+        // for optimization, derive common part only once; the bip32 crate
+        // zeroizes XPrv's internal key material on drop
         let intermediate_key = XPrv::derive_from_path(&master_seed, &"m/44'/1237'/41'".parse()?)?;
         for i in 0..N_DEFAULT {
             // Derive a child key
             let child = intermediate_key.derive_child(ChildNumber::new(i as u32, true)?)?;
-            sk.push(SecretKey::from_slice(&child.private_key().to_bytes()).unwrap());
+            let mut child_bytes = child.private_key().to_bytes();
+            sk.push(SecretKey::from_slice(&child_bytes).unwrap());
+            child_bytes.zeroize();
         }
-        self.generate_levels_internal(sk)
+        let result = self.generate_levels_internal(sk);
+        master_seed.zeroize();
+        result
     }
 
     /// Generate state, hidden secret keys are supplied. Their number also specifies the levels.
-    fn generate_levels_internal(&self, sk: Vec<SecretKey>) -> Result<KeyState, Error> {
+    fn generate_levels_internal(&self, mut sk: Vec<SecretKey>) -> Result<KeyState, Error> {
         let mut keys: Vec<LevelKeys> = Vec::new();
 
         let sk_0_hid = sk[0];
-        let sk_0_vis = sk_0_hid.clone();
-        let mut current = LevelKeys {
-            vis: KeyPair::from_secret_key(&self.secp, &sk_0_vis),
-            hid: KeyPair::from_secret_key(&self.secp, &sk_0_hid),
-        };
-        keys.push(current);
+        let sk_0_vis = sk_0_hid;
+        let mut current = LevelKeys::new(&self.secp, sk_0_vis, sk_0_hid);
+        keys.push(current.clone());
 
         let levels = sk.len();
         for i in 1..levels {
             let next = self.next_level(&current, &sk[i]);
-            keys.push(next);
+            keys.push(next.clone());
             current = next;
         }
 
+        // The scalars themselves are now safely duplicated into `keys`
+        // (each behind a `Zeroizing` buffer); `SecretKey` has no auto-zeroize
+        // of its own, so scrub this `Vec`'s copies before it's dropped.
+        for sk_i in sk.iter_mut() {
+            sk_i.non_secure_erase();
+        }
+
         let n = levels - 1;
         Ok(KeyState { k: keys, n })
     }
@@ -190,10 +399,7 @@ impl KeyManager {
         // Compute new secret key by adding hash value (scalar addition) (sk1 = sk1' + hash)
         let diff = Scalar::from_be_bytes(hash).unwrap();
         let sk_next_vis = sk_next_hid.add_tweak(&diff).unwrap();
-        LevelKeys {
-            vis: KeyPair::from_secret_key(&self.secp, &sk_next_vis),
-            hid: KeyPair::from_secret_key(&self.secp, &sk_next_hid),
-        }
+        LevelKeys::new(&self.secp, sk_next_vis, *sk_next_hid)
     }
 
     /// Perform verification of a newly rotated key
@@ -222,12 +428,163 @@ impl KeyManager {
         // Compare
         (pk_next_odd == *next_visible) || (pk_next_even == *next_visible)
     }
+
+    /// Verify a BIP340 Schnorr signature over a 32-byte event hash, as
+    /// produced by [`KeyState::sign_current`].
+    pub fn verify_signature(
+        &self,
+        pubkey: &XOnlyPublicKey,
+        msg: &[u8; 32],
+        sig: &schnorr::Signature,
+    ) -> bool {
+        let message = Message::from_slice(msg).expect("event hash is always 32 bytes");
+        self.secp.verify_schnorr(sig, &message, pubkey).is_ok()
+    }
+
+    /// Seal the 64-byte master seed and a rotation checkpoint for backup,
+    /// addressed to `recipient_pubkey` (e.g. a cold-storage key, which may
+    /// itself be a NIP-41 visible pubkey).
+    ///
+    /// Uses a single-shot seal modelled on HPKE base mode: an ephemeral
+    /// secp256k1 key agreed with the recipient by ECDH, a plain HKDF-SHA256
+    /// expand of the shared x-coordinate to derive the data-encryption key
+    /// and nonce, and ChaCha20-Poly1305 as the AEAD. It does not use RFC
+    /// 9180's labeled key schedule, so blobs are not interoperable with
+    /// RFC 9180 implementations. The blob is `ephemeral_pubkey || ciphertext`.
+    pub fn export_encrypted(
+        &self,
+        master_seed: &[u8; 64],
+        checkpoint: &Checkpoint,
+        recipient_pubkey: &XOnlyPublicKey,
+    ) -> Result<Vec<u8>, Error> {
+        let mut plaintext = master_seed.to_vec();
+        plaintext.extend_from_slice(
+            &bincode::serialize(checkpoint).map_err(|_| Error::EncryptionFailed)?,
+        );
+
+        let mut eph_sk_bytes = [0u8; 32];
+        thread_rng().fill_bytes(&mut eph_sk_bytes);
+        let eph_sk = SecretKey::from_slice(&eph_sk_bytes).map_err(|_| Error::EncryptionFailed)?;
+        eph_sk_bytes.zeroize();
+        let eph_pk = PublicKey::from_secret_key(&self.secp, &eph_sk);
+
+        // An x-only pubkey doesn't record which Y-parity the real key uses
+        // (this scheme never canonicalizes to one, see `verify`'s two-parity
+        // check), so guessing a parity here and feeding the full point
+        // through `SharedSecret`'s default (parity-dependent) hash would
+        // only decrypt for half of all recipients. Negating a point only
+        // flips its Y, so the shared point's x-coordinate is the same
+        // regardless of which parity we guess; hash only that.
+        let recipient_full = recipient_pubkey.public_key(Parity::Even);
+        let shared = shared_secret_point(&recipient_full, &eph_sk);
+        let (key, nonce) = Self::derive_aead_key_and_nonce(&shared[..32]);
+
+        let cipher = ChaCha20Poly1305::new(&key.into());
+        let ciphertext = cipher
+            .encrypt(&nonce, plaintext.as_ref())
+            .map_err(|_| Error::EncryptionFailed)?;
+        plaintext.zeroize();
+
+        let mut blob = eph_pk.serialize().to_vec();
+        blob.extend_from_slice(&ciphertext);
+        Ok(blob)
+    }
+
+    /// Open a blob produced by [`KeyManager::export_encrypted`] with the
+    /// recipient's secret key, rebuilding the full `KeyState` fast-forwarded
+    /// to the backed-up checkpoint.
+    pub fn import_encrypted(
+        &self,
+        blob: &[u8],
+        recipient_secret: &SecretKey,
+    ) -> Result<KeyState, Error> {
+        const EPH_PUBKEY_LEN: usize = 33;
+        if blob.len() < EPH_PUBKEY_LEN {
+            return Err(Error::DecryptionFailed);
+        }
+        let (eph_pk_bytes, ciphertext) = blob.split_at(EPH_PUBKEY_LEN);
+        let eph_pk = PublicKey::from_slice(eph_pk_bytes).map_err(|_| Error::DecryptionFailed)?;
+
+        let shared = shared_secret_point(&eph_pk, recipient_secret);
+        let (key, nonce) = Self::derive_aead_key_and_nonce(&shared[..32]);
+
+        let cipher = ChaCha20Poly1305::new(&key.into());
+        let mut plaintext = cipher
+            .decrypt(&nonce, ciphertext)
+            .map_err(|_| Error::DecryptionFailed)?;
+
+        if plaintext.len() < 64 {
+            return Err(Error::DecryptionFailed);
+        }
+        let (seed_bytes, checkpoint_bytes) = plaintext.split_at(64);
+        let master_seed: [u8; 64] = seed_bytes.try_into().expect("checked length above");
+        let checkpoint: Checkpoint =
+            bincode::deserialize(checkpoint_bytes).map_err(|_| Error::DecryptionFailed)?;
+
+        let state = self.generate_from_master_seed(master_seed)?;
+        plaintext.zeroize();
+        Self::apply_checkpoint(state, &checkpoint)
+    }
+
+    /// Derive a ChaCha20-Poly1305 key and nonce from an ECDH shared secret
+    /// via HKDF-SHA256, mirroring the KDF step of an HPKE single-shot seal.
+    fn derive_aead_key_and_nonce(shared_secret: &[u8]) -> ([u8; 32], Nonce) {
+        const INFO: &[u8] = b"nip41-proto0/export-v1";
+        let hk = Hkdf::<Sha256>::new(None, shared_secret);
+        let mut okm = [0u8; 44];
+        hk.expand(INFO, &mut okm)
+            .expect("44 bytes is a valid HKDF-SHA256 output length");
+        let mut key = [0u8; 32];
+        key.copy_from_slice(&okm[..32]);
+        let nonce = *Nonce::from_slice(&okm[32..44]);
+        okm.zeroize();
+        (key, nonce)
+    }
+
+    /// Validate a whole migration history in one call: an ordered,
+    /// chronological sequence of `(invalidated visible, its hidden)` pairs,
+    /// as revealed by successive `KeyState::invalidate` calls, tracing back
+    /// to `root_visible` (the very first key the chain started from).
+    ///
+    /// Each step is checked against the one that follows it, applying the
+    /// same hash-tweak relation as [`KeyManager::verify`]. On success,
+    /// returns `steps.last()`'s visible pubkey; on failure, returns the
+    /// index of the first step whose link doesn't hold.
+    ///
+    /// That returned pubkey is the most recent key this chain proves was
+    /// *once* valid, not the signer's still-active current key: the active
+    /// key's hidden half is, by definition, not yet revealed, so it can
+    /// never appear in `steps` for this function to return. A caller
+    /// wanting the live current key needs a fresher signal than a migration
+    /// history (e.g. the signer's own `KeyState::current_visible_pubkey`, or
+    /// a step count from a trusted source). The last step's hidden key is
+    /// not checked, as no later step is there to check it against.
+    pub fn verify_chain(
+        &self,
+        root_visible: &XOnlyPublicKey,
+        steps: &[(XOnlyPublicKey, XOnlyPublicKey)],
+    ) -> Result<XOnlyPublicKey, usize> {
+        let Some((first_visible, _)) = steps.first() else {
+            return Ok(*root_visible);
+        };
+        if first_visible != root_visible {
+            return Err(0);
+        }
+        for i in 0..steps.len() - 1 {
+            let (visible, hidden) = &steps[i];
+            let (next_visible, _) = &steps[i + 1];
+            if !self.verify(visible, hidden, next_visible) {
+                return Err(i);
+            }
+        }
+        Ok(steps[steps.len() - 1].0)
+    }
 }
 
 #[cfg(test)]
 mod test {
     use super::{Error, KeyManager, LevelKeys};
-    use secp256k1::{KeyPair, Scalar, Secp256k1, SecretKey};
+    use secp256k1::{Parity, Scalar, Secp256k1, SecretKey};
 
     /// Some constant, random-generated keys
     const KEY1: &str = "0b441d3662962b4060e15801da6edbf017c14574a03ce8076ceb565fbdad12c1";
@@ -237,16 +594,11 @@ mod test {
     const MNEMO1: &str = "oil oil oil oil oil oil oil oil oil oil oil oil";
 
     fn default_keyset_1_and_2(mgr: &KeyManager) -> LevelKeys {
-        LevelKeys {
-            vis: KeyPair::from_secret_key(
-                &mgr.secp,
-                &SecretKey::from_slice(&hex::decode(KEY1).unwrap()).unwrap(),
-            ),
-            hid: KeyPair::from_secret_key(
-                &mgr.secp,
-                &SecretKey::from_slice(&hex::decode(KEY2).unwrap()).unwrap(),
-            ),
-        }
+        LevelKeys::new(
+            &mgr.secp,
+            SecretKey::from_slice(&hex::decode(KEY1).unwrap()).unwrap(),
+            SecretKey::from_slice(&hex::decode(KEY2).unwrap()).unwrap(),
+        )
     }
 
     #[test]
@@ -260,7 +612,7 @@ mod test {
         let pk = state.current_visible_pubkey().unwrap();
         // check sk-pk
         assert_eq!(
-            sk.x_only_public_key(&mgr.secp).0.serialize(),
+            sk.as_secret_key().x_only_public_key(&mgr.secp).0.serialize(),
             pk.serialize()
         );
     }
@@ -280,6 +632,25 @@ mod test {
         assert!(verify_result);
     }
 
+    #[test]
+    fn sign_current_and_verify() {
+        let mgr = KeyManager::default();
+        let state = mgr.generate_random().unwrap();
+        let pk = state.current_visible_pubkey().unwrap();
+        let msg = [7u8; 32];
+
+        let sig = state.sign_current(&mgr.secp, &msg).unwrap();
+        assert!(mgr.verify_signature(&pk, &msg, &sig));
+
+        // signing again should give the same signature (deterministic nonce)
+        let sig2 = state.sign_current(&mgr.secp, &msg).unwrap();
+        assert_eq!(sig, sig2);
+
+        // a different message should not verify against this signature
+        let other_msg = [8u8; 32];
+        assert!(!mgr.verify_signature(&pk, &other_msg, &sig));
+    }
+
     #[test]
     fn invalidate_and_verify_many() {
         let mgr = KeyManager::default();
@@ -299,6 +670,62 @@ mod test {
         assert_eq!(state.invalidate().err().unwrap(), Error::NoMoreKeyLevels);
     }
 
+    #[test]
+    fn verify_chain_success() {
+        let mgr = KeyManager::default();
+        let mut state = mgr.generate_random().unwrap();
+        let root = state.current_visible_pubkey().unwrap();
+
+        let mut steps = Vec::new();
+        for _ in 0..5 {
+            let (invalid, invalid_hid, _new, _vec) = state.invalidate().unwrap();
+            steps.push((invalid, invalid_hid));
+        }
+
+        let result = mgr.verify_chain(&root, &steps).unwrap();
+        assert_eq!(result, steps.last().unwrap().0);
+    }
+
+    #[test]
+    fn verify_chain_detects_broken_link() {
+        let mgr = KeyManager::default();
+        let mut state = mgr.generate_random().unwrap();
+        let root = state.current_visible_pubkey().unwrap();
+
+        let mut steps = Vec::new();
+        for _ in 0..5 {
+            let (invalid, invalid_hid, _new, _vec) = state.invalidate().unwrap();
+            steps.push((invalid, invalid_hid));
+        }
+        // corrupt the hidden key of the third step
+        steps[2].1 = steps[0].1;
+
+        assert_eq!(mgr.verify_chain(&root, &steps), Err(2));
+    }
+
+    #[test]
+    fn verify_chain_rejects_wrong_root() {
+        let mgr = KeyManager::default();
+        let mut state = mgr.generate_random().unwrap();
+        let _ = state.current_visible_pubkey().unwrap();
+        let (invalid, invalid_hid, _new, _vec) = state.invalidate().unwrap();
+        let (wrong_root, _wrong_hid, _new2, _vec2) = state.invalidate().unwrap();
+
+        assert_eq!(
+            mgr.verify_chain(&wrong_root, &[(invalid, invalid_hid)]),
+            Err(0)
+        );
+    }
+
+    #[test]
+    fn verify_chain_empty_steps_returns_root() {
+        let mgr = KeyManager::default();
+        let state = mgr.generate_random().unwrap();
+        let root = state.current_visible_pubkey().unwrap();
+
+        assert_eq!(mgr.verify_chain(&root, &[]).unwrap(), root);
+    }
+
     #[test]
     fn verify() {
         let mgr = KeyManager::default();
@@ -340,6 +767,115 @@ mod test {
         assert_eq!(pk1, pk2);
     }
 
+    #[test]
+    fn checkpoint_roundtrip() {
+        use super::Checkpoint;
+
+        let mgr = KeyManager::default();
+        let mut state = mgr.generate_from_mnemonic(MNEMO1).unwrap();
+        for _ in 0..3 {
+            state.invalidate().unwrap();
+        }
+        let checkpoint = state.checkpoint();
+
+        // JSON (human-readable): fingerprint round-trips through hex
+        let json = serde_json::to_string(&checkpoint).unwrap();
+        let from_json: Checkpoint = serde_json::from_str(&json).unwrap();
+        assert_eq!(from_json, checkpoint);
+
+        let restored = mgr
+            .restore_from_mnemonic_and_checkpoint(MNEMO1, &checkpoint)
+            .unwrap();
+        assert_eq!(
+            restored.current_visible_pubkey().unwrap(),
+            state.current_visible_pubkey().unwrap()
+        );
+    }
+
+    #[test]
+    fn checkpoint_mismatched_mnemonic_rejected() {
+        let mgr = KeyManager::default();
+        let state = mgr.generate_from_mnemonic(MNEMO1).unwrap();
+        let checkpoint = state.checkpoint();
+
+        let other_mnemonic =
+            "legal winner thank year wave sausage worth useful legal winner thank yellow";
+        assert_eq!(
+            mgr.restore_from_mnemonic_and_checkpoint(other_mnemonic, &checkpoint)
+                .unwrap_err(),
+            Error::CheckpointMismatch
+        );
+    }
+
+    #[test]
+    fn export_import_encrypted_roundtrip() {
+        let mgr = KeyManager::default();
+        let master_seed: [u8; 64] = hex::decode(SEED1).unwrap().try_into().unwrap();
+        let mut state = mgr.generate_from_master_seed(master_seed).unwrap();
+        state.invalidate().unwrap();
+        let checkpoint = state.checkpoint();
+
+        let recipient_secret = SecretKey::from_slice(&hex::decode(KEY1).unwrap()).unwrap();
+        let recipient_pubkey = recipient_secret.x_only_public_key(&mgr.secp).0;
+
+        let blob = mgr
+            .export_encrypted(&master_seed, &checkpoint, &recipient_pubkey)
+            .unwrap();
+        let recovered = mgr.import_encrypted(&blob, &recipient_secret).unwrap();
+
+        assert_eq!(
+            recovered.current_visible_pubkey().unwrap(),
+            state.current_visible_pubkey().unwrap()
+        );
+    }
+
+    #[test]
+    fn export_import_encrypted_roundtrip_odd_parity_recipient() {
+        // KEY2's real public key has odd Y-parity, unlike KEY1 (used above),
+        // which exercises the case where `export_encrypted`'s even-Y guess
+        // for the recipient's full point doesn't match the real key.
+        let mgr = KeyManager::default();
+        let master_seed: [u8; 64] = hex::decode(SEED1).unwrap().try_into().unwrap();
+        let mut state = mgr.generate_from_master_seed(master_seed).unwrap();
+        state.invalidate().unwrap();
+        let checkpoint = state.checkpoint();
+
+        let recipient_secret = SecretKey::from_slice(&hex::decode(KEY2).unwrap()).unwrap();
+        let (recipient_pubkey, recipient_parity) =
+            recipient_secret.x_only_public_key(&mgr.secp);
+        assert_eq!(recipient_parity, Parity::Odd);
+
+        let blob = mgr
+            .export_encrypted(&master_seed, &checkpoint, &recipient_pubkey)
+            .unwrap();
+        let recovered = mgr.import_encrypted(&blob, &recipient_secret).unwrap();
+
+        assert_eq!(
+            recovered.current_visible_pubkey().unwrap(),
+            state.current_visible_pubkey().unwrap()
+        );
+    }
+
+    #[test]
+    fn import_encrypted_rejects_wrong_key() {
+        let mgr = KeyManager::default();
+        let master_seed: [u8; 64] = hex::decode(SEED1).unwrap().try_into().unwrap();
+        let state = mgr.generate_from_master_seed(master_seed).unwrap();
+        let checkpoint = state.checkpoint();
+
+        let recipient_secret = SecretKey::from_slice(&hex::decode(KEY1).unwrap()).unwrap();
+        let recipient_pubkey = recipient_secret.x_only_public_key(&mgr.secp).0;
+        let wrong_secret = SecretKey::from_slice(&hex::decode(KEY2).unwrap()).unwrap();
+
+        let blob = mgr
+            .export_encrypted(&master_seed, &checkpoint, &recipient_pubkey)
+            .unwrap();
+        assert_eq!(
+            mgr.import_encrypted(&blob, &wrong_secret).unwrap_err(),
+            Error::DecryptionFailed
+        );
+    }
+
     #[test]
     fn generate_master_seed() {
         let master_seed: [u8; 64] = hex::decode(SEED1).unwrap().try_into().unwrap();
@@ -367,11 +903,11 @@ mod test {
 
         let next = mgr.next_level(&current, &sk_next_t);
         assert_eq!(
-            hex::encode(next.vis.secret_key().secret_bytes()),
+            hex::encode(next.vis_secret_key().as_secret_key().secret_bytes()),
             "bf0c756639fa5542a5839ab6825258f21056a72cabec222a50b9e7b07a1eb09e"
         );
         assert_eq!(
-            hex::encode(next.hid.secret_key().secret_bytes()),
+            hex::encode(next.hid_secret_key().as_secret_key().secret_bytes()),
             "26d5cf30786a9d2c6f6ef3dffa687257d5ec3baae9e30a3f74d96bbae192f3a7"
         );
     }